@@ -0,0 +1,61 @@
+extern crate gl_generator;
+
+use gl_generator::{Api, Fallbacks, Profile, Registry, StructGenerator, DebugStructGenerator};
+use std::env;
+use std::fs::File;
+use std::path::Path;
+
+/**
+ * Generates a struct-based GL loader (`gl_bindings::Gl`) instead of the process-global
+ * function pointers the `gl` crate uses, so each `GlWindow` can own its own set of
+ * loaded functions rather than sharing one global table across every context in the
+ * process. Profile, target version, and extensions are all configurable through
+ * environment variables so downstream consumers aren't stuck with the demo's defaults.
+ *
+ * With the `gl_debug` feature enabled the bindings are emitted through
+ * `DebugStructGenerator` instead, which prints every call to stdout and, after each
+ * one, reads `glGetError` and prints any error code it finds - on every call rather
+ * than just the ones the demo wraps in `gl_check!`. It does not panic. Since that
+ * read clears the error flag, `gl_check!`'s own `glGetError` loop normally finds
+ * nothing left to report in these builds.
+ */
+fn main() {
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let dest = Path::new(&out_dir).join("gl_bindings.rs");
+    let mut file = File::create(&dest).unwrap();
+
+    let profile = match env::var("GL_PROFILE").as_ref().map(String::as_str) {
+        Ok("compatibility") => Profile::Compatibility,
+        _ => Profile::Core,
+    };
+
+    let major = env::var("GL_VERSION_MAJOR")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(3u8);
+    let minor = env::var("GL_VERSION_MINOR")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(3u8);
+
+    let mut extensions: Vec<String> = env::var("GL_EXTENSIONS")
+        .map(|v| v.split(',').map(str::to_string).filter(|s| !s.is_empty()).collect())
+        .unwrap_or_else(|_| Vec::new());
+
+    if env::var("CARGO_FEATURE_GL_DEBUG").is_ok() && !extensions.iter().any(|e| e == "GL_KHR_debug") {
+        extensions.push("GL_KHR_debug".to_string());
+    }
+
+    let registry = Registry::new(Api::Gl, (major, minor), profile, Fallbacks::All, extensions);
+
+    if env::var("CARGO_FEATURE_GL_DEBUG").is_ok() {
+        registry.write_bindings(DebugStructGenerator, &mut file).unwrap();
+    } else {
+        registry.write_bindings(StructGenerator, &mut file).unwrap();
+    }
+
+    println!("cargo:rerun-if-env-changed=GL_PROFILE");
+    println!("cargo:rerun-if-env-changed=GL_VERSION_MAJOR");
+    println!("cargo:rerun-if-env-changed=GL_VERSION_MINOR");
+    println!("cargo:rerun-if-env-changed=GL_EXTENSIONS");
+}
@@ -1,9 +1,15 @@
 extern crate gdk;
 extern crate gtk;
 
-extern crate gl;
 extern crate glutin;
 
+#[cfg(feature = "glutin_backend")]
+extern crate winit;
+
+#[cfg(feature = "gl_debug")]
+#[macro_use]
+extern crate log;
+
 
 #[cfg(feature = "gtk_3_16")]
 use gdk::{GLContextExt,
@@ -16,16 +22,726 @@ use gtk::{ContainerExt,
           Window, GtkWindowExt,
           WindowType};
 
-use gl::types::*;
+#[cfg(feature = "glutin_backend")]
+use glutin::{ContextBuilder, GlWindow as GlutinWindow};
+
+#[cfg(feature = "glutin_backend")]
+use winit::{EventsLoop, Event, WindowBuilder, WindowEvent};
+
 use glutin::{Api, GlContext, GlRequest};
 
-use std::mem;
 use std::ptr;
 use std::str;
 use std::ffi::CString;
 use std::os::raw::c_void;
 use std::time::SystemTime;
 use std::sync::{Arc, Mutex};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "glutin_backend")]
+use std::cell::RefCell;
+
+#[cfg(feature = "gl_debug")]
+use std::ffi::CStr;
+
+/**
+ * The loader generated by `build.rs` from `gl_generator`, scoped behind a module instead
+ * of the `gl` crate's process-global function pointers. Every `GlWindow` loads and owns
+ * its own `gl::Gl` instance (see `Gl::load_with` call sites below) so multiple contexts
+ * in the same process never fight over one global table. Constants (`gl::TRIANGLES`,
+ * `gl::types::*`, ...) are still free items in this module, so those paths are unchanged;
+ * only actual GL calls become `<gl instance>.SomeFunction(...)` method calls.
+ */
+mod gl {
+    #![allow(non_snake_case, non_camel_case_types, non_upper_case_globals, dead_code)]
+    include!(concat!(env!("OUT_DIR"), "/gl_bindings.rs"));
+}
+
+use gl::types::*;
+use gl::Gl;
+
+/**
+ * `gl_check!(gl, call)` wraps a raw `gl.*` call and, when the `gl_debug` feature is
+ * enabled, drains `glGetError` on `gl` after it and logs any error with the file/line
+ * that made it. In those builds the bindings come from `DebugStructGenerator` (see
+ * `build.rs`), whose wrappers already read and print `glGetError` after every call,
+ * so the error flag is normally cleared before this loop looks at it. With
+ * `gl_debug` off it's a plain passthrough: no per-call overhead and no error
+ * checking (and no `KHR_debug` callback, since `enable_debug_output` is only
+ * compiled with `gl_debug`). The `Gl` instance is always an explicit first
+ * argument rather than a `gl` name the macro assumes is in scope, so the dependency
+ * is visible at every call site.
+ */
+#[cfg(feature = "gl_debug")]
+macro_rules! gl_check {
+    ($gl:expr, $call:expr) => {{
+        let result = $call;
+        let mut error = $gl.GetError();
+        while error != gl::NO_ERROR {
+            error!(
+                "gl error {:#x} at {}:{}: `{}`",
+                error,
+                file!(),
+                line!(),
+                stringify!($call)
+            );
+            error = $gl.GetError();
+        }
+        result
+    }};
+}
+
+#[cfg(not(feature = "gl_debug"))]
+macro_rules! gl_check {
+    ($gl:expr, $call:expr) => {
+        $call
+    };
+}
+
+#[cfg(feature = "gl_debug")]
+fn debug_source_str(source: GLenum) -> &'static str {
+    match source {
+        gl::DEBUG_SOURCE_API => "api",
+        gl::DEBUG_SOURCE_WINDOW_SYSTEM => "window system",
+        gl::DEBUG_SOURCE_SHADER_COMPILER => "shader compiler",
+        gl::DEBUG_SOURCE_THIRD_PARTY => "third party",
+        gl::DEBUG_SOURCE_APPLICATION => "application",
+        _ => "other",
+    }
+}
+
+#[cfg(feature = "gl_debug")]
+fn debug_type_str(ty: GLenum) -> &'static str {
+    match ty {
+        gl::DEBUG_TYPE_ERROR => "error",
+        gl::DEBUG_TYPE_DEPRECATED_BEHAVIOR => "deprecated behavior",
+        gl::DEBUG_TYPE_UNDEFINED_BEHAVIOR => "undefined behavior",
+        gl::DEBUG_TYPE_PORTABILITY => "portability",
+        gl::DEBUG_TYPE_PERFORMANCE => "performance",
+        gl::DEBUG_TYPE_MARKER => "marker",
+        _ => "other",
+    }
+}
+
+#[cfg(feature = "gl_debug")]
+extern "system" fn debug_message_callback(
+    source: GLenum,
+    ty: GLenum,
+    id: GLuint,
+    severity: GLenum,
+    _length: GLsizei,
+    message: *const GLchar,
+    _user_param: *mut c_void,
+) {
+    let message = unsafe { CStr::from_ptr(message).to_string_lossy() };
+    let source = debug_source_str(source);
+    let ty = debug_type_str(ty);
+
+    match severity {
+        gl::DEBUG_SEVERITY_HIGH => error!("[{}:{}] ({}) {}", source, ty, id, message),
+        gl::DEBUG_SEVERITY_MEDIUM => warn!("[{}:{}] ({}) {}", source, ty, id, message),
+        gl::DEBUG_SEVERITY_LOW => info!("[{}:{}] ({}) {}", source, ty, id, message),
+        _ => debug!("[{}:{}] ({}) {}", source, ty, id, message),
+    }
+}
+
+/**
+ * Registers `debug_message_callback` with `GL_KHR_debug`. Must be called after a
+ * debug-flagged context is current and its functions are loaded, which is why it's
+ * invoked from each backend's realize hook rather than from `init()`.
+ */
+#[cfg(feature = "gl_debug")]
+pub fn enable_debug_output(gl: &Gl) {
+    unsafe {
+        gl.Enable(gl::DEBUG_OUTPUT);
+        gl.Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
+        gl.DebugMessageCallback(Some(debug_message_callback), ptr::null());
+    }
+}
+
+/**
+ * Carries the `glGetShaderInfoLog`/`glGetProgramInfoLog` text for a failed compile or
+ * link, so callers can decide whether to recover (keep the previous program, report
+ * to the user, retry) instead of the whole demo panicking on a shader typo.
+ */
+#[derive(Debug)]
+pub struct ShaderError {
+    pub log: String,
+}
+
+impl std::fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.log)
+    }
+}
+
+impl std::error::Error for ShaderError {}
+
+/**
+ * A small preprocessing pass for `.vert`/`.frag` files, inspired by the minification
+ * step 4K intros run over their shaders: strip `//` and `/* */` comments, resolve
+ * `#include "file"` relative to the including file's directory, and collapse blank
+ * lines before the result ever reaches `compile_shader`.
+ */
+#[cfg(any(feature = "gtk_3_16", feature = "glutin_backend"))]
+mod shader {
+    use std::collections::HashSet;
+    use std::fs;
+    use std::io;
+    use std::path::{Path, PathBuf};
+
+    pub fn preprocess(path: &Path) -> io::Result<String> {
+        let mut seen = HashSet::new();
+        preprocess_file(path, &mut seen)
+    }
+
+    fn preprocess_file(path: &Path, seen: &mut HashSet<PathBuf>) -> io::Result<String> {
+        let canonical = path.canonicalize()?;
+        if !seen.insert(canonical) {
+            return Ok(String::new());
+        }
+
+        let dir = path.parent().unwrap_or_else(|| Path::new("."));
+        let source = fs::read_to_string(path)?;
+        let mut out = String::with_capacity(source.len());
+
+        for line in strip_comments(&source).lines() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+
+            if let Some(included) = parse_include(trimmed) {
+                out.push_str(&preprocess_file(&dir.join(included), seen)?);
+                continue;
+            }
+
+            out.push_str(trimmed);
+            out.push('\n');
+        }
+
+        Ok(out)
+    }
+
+    fn parse_include(line: &str) -> Option<&str> {
+        line.strip_prefix("#include")?
+            .trim()
+            .strip_prefix('"')?
+            .strip_suffix('"')
+    }
+
+    fn strip_comments(source: &str) -> String {
+        let mut out = String::with_capacity(source.len());
+        let mut chars = source.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '/' if chars.peek() == Some(&'/') => {
+                    while let Some(&next) = chars.peek() {
+                        if next == '\n' {
+                            break;
+                        }
+                        chars.next();
+                    }
+                }
+                '/' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    while let Some(next) = chars.next() {
+                        if next == '*' && chars.peek() == Some(&'/') {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                _ => out.push(c),
+            }
+        }
+
+        out
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn scratch_dir(name: &str) -> PathBuf {
+            let mut dir = std::env::temp_dir();
+            dir.push(format!("glarea_shader_test_{}_{}", std::process::id(), name));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            dir
+        }
+
+        fn write(dir: &Path, name: &str, contents: &str) -> PathBuf {
+            let path = dir.join(name);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).unwrap();
+            }
+            fs::write(&path, contents).unwrap();
+            path
+        }
+
+        #[test]
+        fn strip_comments_removes_line_and_block_comments() {
+            let source = "a // line comment\nb /* block\ncomment */ c\n";
+            assert_eq!(strip_comments(source), "a \nb  c\n");
+        }
+
+        #[test]
+        fn preprocess_trims_and_collapses_blank_lines() {
+            let dir = scratch_dir("blank_lines");
+            let path = write(&dir, "main.glsl", "  a  \n\n\n  b  \n");
+            assert_eq!(preprocess(&path).unwrap(), "a\nb\n");
+        }
+
+        #[test]
+        fn preprocess_resolves_includes_relative_to_each_file() {
+            let dir = scratch_dir("includes");
+            write(&dir, "sub/c.glsl", "nested\n");
+            write(&dir, "sub/b.glsl", "#include \"c.glsl\"\nvalue\n");
+            let main = write(&dir, "a.glsl", "#include \"sub/b.glsl\"\nmain\n");
+
+            assert_eq!(preprocess(&main).unwrap(), "nested\nvalue\nmain\n");
+        }
+
+        #[test]
+        fn preprocess_breaks_self_include_cycles() {
+            let dir = scratch_dir("cycle");
+            let path = write(&dir, "cyclic.glsl", "#include \"cyclic.glsl\"\nbody\n");
+            assert_eq!(preprocess(&path).unwrap(), "body\n");
+        }
+    }
+}
+
+/**
+ * Polled once per `connect_render` tick. If either shader file's mtime has moved since
+ * the last check, re-preprocesses and recompiles both and hands back the newly linked
+ * program. Compile/link failures are reported and swallowed so the caller just keeps
+ * running the previous program instead of crashing mid-edit.
+ */
+#[cfg(any(feature = "gtk_3_16", feature = "glutin_backend"))]
+pub struct ShaderHotReloader {
+    gl: Arc<Gl>,
+    vert_path: PathBuf,
+    frag_path: PathBuf,
+    vert_mtime: SystemTime,
+    frag_mtime: SystemTime,
+}
+
+#[cfg(any(feature = "gtk_3_16", feature = "glutin_backend"))]
+impl ShaderHotReloader {
+    pub fn new(gl: Arc<Gl>, vert_path: PathBuf, frag_path: PathBuf) -> io::Result<ShaderHotReloader> {
+        let vert_mtime = fs::metadata(&vert_path)?.modified()?;
+        let frag_mtime = fs::metadata(&frag_path)?.modified()?;
+
+        Ok(ShaderHotReloader {
+            gl: gl,
+            vert_path: vert_path,
+            frag_path: frag_path,
+            vert_mtime: vert_mtime,
+            frag_mtime: frag_mtime,
+        })
+    }
+
+    pub fn poll(&mut self) -> Option<gl_object::Program> {
+        let vert_mtime = fs::metadata(&self.vert_path).and_then(|m| m.modified()).ok()?;
+        let frag_mtime = fs::metadata(&self.frag_path).and_then(|m| m.modified()).ok()?;
+
+        if vert_mtime == self.vert_mtime && frag_mtime == self.frag_mtime {
+            return None;
+        }
+
+        self.vert_mtime = vert_mtime;
+        self.frag_mtime = frag_mtime;
+
+        match self.recompile() {
+            Ok(prog) => Some(prog),
+            Err(e) => {
+                eprintln!("shader hot-reload failed, keeping previous program:\n{}", e);
+                None
+            }
+        }
+    }
+
+    fn recompile(&self) -> Result<gl_object::Program, String> {
+        let vert_src = shader::preprocess(&self.vert_path).map_err(|e| e.to_string())?;
+        let frag_src = shader::preprocess(&self.frag_path).map_err(|e| e.to_string())?;
+
+        let vert_shader = compile_shader(&self.gl, &vert_src, gl::VERTEX_SHADER).map_err(|e| e.to_string())?;
+        let frag_shader = compile_shader(&self.gl, &frag_src, gl::FRAGMENT_SHADER).map_err(|e| e.to_string())?;
+
+        gl_object::Program::link(&self.gl, vert_shader, frag_shader).map_err(|e| e.to_string())
+    }
+}
+
+/**
+ * RAII wrappers over the raw `GLuint` handles the demo used to juggle by hand. Each
+ * type owns its handle, deletes it in `Drop`, and hands out a scoped binding guard
+ * that unbinds on scope exit instead of requiring a matching manual unbind at every
+ * call site. Modeled on the `OpenGLProgram`/`OpenGLTexture`/`OpenGLFramebuffer` split
+ * LibreOffice uses for the same reason.
+ */
+#[cfg(any(feature = "gtk_3_16", feature = "glutin_backend"))]
+mod gl_object {
+    use gl::{self, Gl};
+    use gl::types::*;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::ffi::CString;
+    use std::mem;
+    use std::os::raw::c_void;
+    use std::sync::Arc;
+
+    use super::ShaderError;
+
+    pub struct Buffer {
+        gl: Arc<Gl>,
+        handle: GLuint,
+        target: GLenum,
+    }
+
+    impl Buffer {
+        pub fn new(gl: &Arc<Gl>, target: GLenum) -> Buffer {
+            let mut handle = 0;
+            unsafe {
+                gl.GenBuffers(1, &mut handle);
+            }
+            Buffer { gl: gl.clone(), handle: handle, target: target }
+        }
+
+        pub fn handle(&self) -> GLuint {
+            self.handle
+        }
+
+        pub fn bind(&self) -> BufferBinding {
+            unsafe {
+                self.gl.BindBuffer(self.target, self.handle);
+            }
+            BufferBinding { gl: self.gl.clone(), target: self.target }
+        }
+
+        pub fn set_data<T>(&self, data: &[T], usage: GLenum) {
+            let _binding = self.bind();
+            unsafe {
+                self.gl.BufferData(
+                    self.target,
+                    (data.len() * mem::size_of::<T>()) as GLsizeiptr,
+                    data.as_ptr() as *const c_void,
+                    usage,
+                );
+            }
+        }
+    }
+
+    impl Drop for Buffer {
+        fn drop(&mut self) {
+            unsafe {
+                self.gl.DeleteBuffers(1, &self.handle);
+            }
+        }
+    }
+
+    #[must_use]
+    pub struct BufferBinding {
+        gl: Arc<Gl>,
+        target: GLenum,
+    }
+
+    impl Drop for BufferBinding {
+        fn drop(&mut self) {
+            unsafe {
+                self.gl.BindBuffer(self.target, 0);
+            }
+        }
+    }
+
+    pub struct VertexArray {
+        gl: Arc<Gl>,
+        handle: GLuint,
+    }
+
+    impl VertexArray {
+        pub fn new(gl: &Arc<Gl>) -> VertexArray {
+            let mut handle = 0;
+            unsafe {
+                gl.GenVertexArrays(1, &mut handle);
+            }
+            VertexArray { gl: gl.clone(), handle: handle }
+        }
+
+        pub fn handle(&self) -> GLuint {
+            self.handle
+        }
+
+        pub fn bind(&self) -> VertexArrayBinding {
+            unsafe {
+                self.gl.BindVertexArray(self.handle);
+            }
+            VertexArrayBinding { gl: self.gl.clone() }
+        }
+    }
+
+    impl Drop for VertexArray {
+        fn drop(&mut self) {
+            unsafe {
+                self.gl.DeleteVertexArrays(1, &self.handle);
+            }
+        }
+    }
+
+    #[must_use]
+    pub struct VertexArrayBinding {
+        gl: Arc<Gl>,
+    }
+
+    impl Drop for VertexArrayBinding {
+        fn drop(&mut self) {
+            unsafe {
+                self.gl.BindVertexArray(0);
+            }
+        }
+    }
+
+    /**
+     * Owns a linked program and caches uniform/attribute locations by name, so
+     * repeated `set_uniform_*` calls in a render loop don't re-query the driver every
+     * frame the way the raw-handle version implicitly invited.
+     */
+    pub struct Program {
+        gl: Arc<Gl>,
+        handle: GLuint,
+        uniforms: RefCell<HashMap<String, GLint>>,
+        attribs: RefCell<HashMap<String, GLint>>,
+    }
+
+    impl Program {
+        pub fn link(gl: &Arc<Gl>, vs: GLuint, fs: GLuint) -> Result<Program, ShaderError> {
+            let handle = super::link_program(gl, vs, fs)?;
+            Ok(Program {
+                gl: gl.clone(),
+                handle: handle,
+                uniforms: RefCell::new(HashMap::new()),
+                attribs: RefCell::new(HashMap::new()),
+            })
+        }
+
+        pub fn handle(&self) -> GLuint {
+            self.handle
+        }
+
+        pub fn bind(&self) -> ProgramBinding {
+            unsafe {
+                self.gl.UseProgram(self.handle);
+            }
+            ProgramBinding { gl: self.gl.clone() }
+        }
+
+        pub fn uniform_location(&self, name: &str) -> GLint {
+            if let Some(&loc) = self.uniforms.borrow().get(name) {
+                return loc;
+            }
+
+            let c_name = CString::new(name).unwrap();
+            let loc = unsafe { self.gl.GetUniformLocation(self.handle, c_name.as_ptr()) };
+            self.uniforms.borrow_mut().insert(name.to_string(), loc);
+            loc
+        }
+
+        pub fn attrib_location(&self, name: &str) -> GLint {
+            if let Some(&loc) = self.attribs.borrow().get(name) {
+                return loc;
+            }
+
+            let c_name = CString::new(name).unwrap();
+            let loc = unsafe { self.gl.GetAttribLocation(self.handle, c_name.as_ptr()) };
+            self.attribs.borrow_mut().insert(name.to_string(), loc);
+            loc
+        }
+
+        pub fn set_uniform_1i(&self, name: &str, value: GLint) {
+            let loc = self.uniform_location(name);
+            unsafe {
+                self.gl.Uniform1i(loc, value);
+            }
+        }
+
+        pub fn set_uniform_1f(&self, name: &str, value: GLfloat) {
+            let loc = self.uniform_location(name);
+            unsafe {
+                self.gl.Uniform1f(loc, value);
+            }
+        }
+
+        pub fn set_uniform_4f(&self, name: &str, x: GLfloat, y: GLfloat, z: GLfloat, w: GLfloat) {
+            let loc = self.uniform_location(name);
+            unsafe {
+                self.gl.Uniform4f(loc, x, y, z, w);
+            }
+        }
+    }
+
+    impl Drop for Program {
+        fn drop(&mut self) {
+            unsafe {
+                self.gl.DeleteProgram(self.handle);
+            }
+        }
+    }
+
+    #[must_use]
+    pub struct ProgramBinding {
+        gl: Arc<Gl>,
+    }
+
+    impl Drop for ProgramBinding {
+        fn drop(&mut self) {
+            unsafe {
+                self.gl.UseProgram(0);
+            }
+        }
+    }
+
+    #[allow(dead_code)] // not yet exercised by the quad demo; rounding out the wrapper set
+    pub struct Texture {
+        gl: Arc<Gl>,
+        handle: GLuint,
+        target: GLenum,
+    }
+
+    #[allow(dead_code)]
+    impl Texture {
+        pub fn new(gl: &Arc<Gl>, target: GLenum) -> Texture {
+            let mut handle = 0;
+            unsafe {
+                gl.GenTextures(1, &mut handle);
+            }
+            Texture { gl: gl.clone(), handle: handle, target: target }
+        }
+
+        pub fn handle(&self) -> GLuint {
+            self.handle
+        }
+
+        pub fn bind(&self) -> TextureBinding {
+            unsafe {
+                self.gl.BindTexture(self.target, self.handle);
+            }
+            TextureBinding { gl: self.gl.clone(), target: self.target }
+        }
+    }
+
+    impl Drop for Texture {
+        fn drop(&mut self) {
+            unsafe {
+                self.gl.DeleteTextures(1, &self.handle);
+            }
+        }
+    }
+
+    #[must_use]
+    #[allow(dead_code)]
+    pub struct TextureBinding {
+        gl: Arc<Gl>,
+        target: GLenum,
+    }
+
+    impl Drop for TextureBinding {
+        fn drop(&mut self) {
+            unsafe {
+                self.gl.BindTexture(self.target, 0);
+            }
+        }
+    }
+
+    /**
+     * Bundles an FBO with the color/depth renderbuffers attached to it, owning all
+     * three so `render_scene_to_file` doesn't have to delete them by hand on every
+     * exit path (including the panicking ones).
+     */
+    pub struct Framebuffer {
+        gl: Arc<Gl>,
+        handle: GLuint,
+        color_rbo: GLuint,
+        depth_rbo: GLuint,
+    }
+
+    impl Framebuffer {
+        pub fn with_renderbuffers(gl: &Arc<Gl>, width: GLsizei, height: GLsizei) -> Framebuffer {
+            let mut handle = 0;
+            let mut color_rbo = 0;
+            let mut depth_rbo = 0;
+
+            unsafe {
+                gl.GenFramebuffers(1, &mut handle);
+                gl.GenRenderbuffers(1, &mut color_rbo);
+                gl.GenRenderbuffers(1, &mut depth_rbo);
+
+                gl.BindRenderbuffer(gl::RENDERBUFFER, color_rbo);
+                gl.RenderbufferStorage(gl::RENDERBUFFER, gl::RGBA8, width, height);
+
+                gl.BindRenderbuffer(gl::RENDERBUFFER, depth_rbo);
+                gl.RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH_COMPONENT24, width, height);
+
+                gl.BindFramebuffer(gl::FRAMEBUFFER, handle);
+                gl.FramebufferRenderbuffer(
+                    gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::RENDERBUFFER, color_rbo,
+                );
+                gl.FramebufferRenderbuffer(
+                    gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, gl::RENDERBUFFER, depth_rbo,
+                );
+
+                if gl.CheckFramebufferStatus(gl::FRAMEBUFFER) != gl::FRAMEBUFFER_COMPLETE {
+                    panic!("offscreen framebuffer is incomplete");
+                }
+
+                gl.BindFramebuffer(gl::FRAMEBUFFER, 0);
+            }
+
+            Framebuffer {
+                gl: gl.clone(),
+                handle: handle,
+                color_rbo: color_rbo,
+                depth_rbo: depth_rbo,
+            }
+        }
+
+        pub fn handle(&self) -> GLuint {
+            self.handle
+        }
+
+        pub fn bind(&self) -> FramebufferBinding {
+            unsafe {
+                self.gl.BindFramebuffer(gl::FRAMEBUFFER, self.handle);
+            }
+            FramebufferBinding { gl: self.gl.clone() }
+        }
+    }
+
+    impl Drop for Framebuffer {
+        fn drop(&mut self) {
+            unsafe {
+                self.gl.DeleteFramebuffers(1, &self.handle);
+                self.gl.DeleteRenderbuffers(1, &self.color_rbo);
+                self.gl.DeleteRenderbuffers(1, &self.depth_rbo);
+            }
+        }
+    }
+
+    #[must_use]
+    pub struct FramebufferBinding {
+        gl: Arc<Gl>,
+    }
+
+    impl Drop for FramebufferBinding {
+        fn drop(&mut self) {
+            unsafe {
+                self.gl.BindFramebuffer(gl::FRAMEBUFFER, 0);
+            }
+        }
+    }
+}
 
 #[cfg(feature = "gtk_3_16")]
 pub struct GlWindow {
@@ -69,81 +785,401 @@ impl GlWindow {
         self.show();
         gtk::main();
     }
+
+    /// Doesn't touch `self` on purpose: rendering to a file uses its own headless
+    /// context (see `render_scene_to_file`), so it mustn't require a GTK/glutin
+    /// window (and the display that comes with it) to already exist. `path` must
+    /// have a `.ppm` extension — output is always binary PPM, never PNG.
+    pub fn render_to_file(width: u32, height: u32, path: &Path) -> io::Result<()> {
+        render_scene_to_file(width, height, path)
+    }
 }
 
 
-#[cfg(feature = "gtk_3_16")]
-pub fn compile_shader(src: &str, ty: GLenum) -> GLuint {
+/**
+ * `glutin_backend` wires up the same init()/show()/exec() surface and the same
+ * create-context/resize/realize/render hooks as the GTK `GLArea` path above, but
+ * without depending on GTK at all. Since `glutin::GlWindow` bundles a window and its
+ * GL context together (there's no separate "realize" signal like GTK's), the hooks
+ * are just callbacks we store and fire ourselves at the equivalent point in `exec()`.
+ */
+#[cfg(feature = "glutin_backend")]
+pub struct GlWindow {
+    pub window: GlutinWindow,
+    gl: RefCell<Option<Arc<Gl>>>,
+    events_loop: RefCell<EventsLoop>,
+    on_create_context: RefCell<Option<Box<dyn FnOnce(&GlutinWindow)>>>,
+    on_resize: RefCell<Option<Box<dyn FnMut(&Gl, u32, u32)>>>,
+    on_realize: RefCell<Option<Box<dyn FnOnce(&Arc<Gl>, &GlutinWindow)>>>,
+    on_render: RefCell<Option<Box<dyn FnMut(&Gl, &GlutinWindow)>>>,
+}
+
+#[cfg(feature = "glutin_backend")]
+impl GlWindow {
+    pub fn init() -> GlWindow {
+        let events_loop = EventsLoop::new();
+
+        let window_builder = WindowBuilder::new()
+            .with_title("OpenGL Demo")
+            .with_dimensions((1200, 800).into());
+
+        let context_builder = ContextBuilder::new()
+            .with_gl(GlRequest::Specific(Api::OpenGl, (3, 3)));
+
+        #[cfg(feature = "gl_debug")]
+        let context_builder = context_builder.with_gl_debug_flag(true);
+
+        let window = GlutinWindow::new(window_builder, context_builder, &events_loop)
+            .expect("Failed to create glutin window");
+
+        GlWindow {
+            window: window,
+            gl: RefCell::new(None),
+            events_loop: RefCell::new(events_loop),
+            on_create_context: RefCell::new(None),
+            on_resize: RefCell::new(None),
+            on_realize: RefCell::new(None),
+            on_render: RefCell::new(None),
+        }
+    }
+
+    pub fn connect_create_context<F: FnOnce(&GlutinWindow) + 'static>(&self, f: F) {
+        *self.on_create_context.borrow_mut() = Some(Box::new(f));
+    }
+
+    pub fn connect_resize<F: FnMut(&Gl, u32, u32) + 'static>(&self, f: F) {
+        *self.on_resize.borrow_mut() = Some(Box::new(f));
+    }
+
+    pub fn connect_realize<F: FnOnce(&Arc<Gl>, &GlutinWindow) + 'static>(&self, f: F) {
+        *self.on_realize.borrow_mut() = Some(Box::new(f));
+    }
+
+    pub fn connect_render<F: FnMut(&Gl, &GlutinWindow) + 'static>(&self, f: F) {
+        *self.on_render.borrow_mut() = Some(Box::new(f));
+    }
+
+    pub fn show(&self) {
+        self.window.show();
+    }
+
+    pub fn exec(&self) {
+        self.show();
+
+        unsafe {
+            self.window.make_current().expect("Failed to make context current");
+        }
+
+        if let Some(f) = self.on_create_context.borrow_mut().take() {
+            f(&self.window);
+        }
+
+        /**
+         * Each `GlWindow` loads its own `Gl` instance right after its context becomes
+         * current, rather than relying on the `gl` crate's process-global function
+         * pointers - so a second window (or the headless context `render_to_file`
+         * spins up) never has to worry about which one most recently called
+         * `gl::load_with`.
+         */
+        let gl = Arc::new(Gl::load_with(|s| {
+            self.window.context().get_proc_address(s) as *const c_void
+        }));
+        *self.gl.borrow_mut() = Some(gl.clone());
+
+        if let Some(f) = self.on_realize.borrow_mut().take() {
+            f(&gl, &self.window);
+        }
+
+        let mut running = true;
+        while running {
+            let window = &self.window;
+            let on_resize = &self.on_resize;
+
+            self.events_loop.borrow_mut().poll_events(|event| {
+                if let Event::WindowEvent { event, .. } = event {
+                    match event {
+                        WindowEvent::CloseRequested => running = false,
+                        WindowEvent::Resized(size) => {
+                            let physical = size.to_physical(window.get_hidpi_factor());
+                            window.resize(physical);
+
+                            if let Some(ref mut f) = *on_resize.borrow_mut() {
+                                f(&gl, physical.width as u32, physical.height as u32);
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+            });
+
+            if let Some(ref mut f) = *self.on_render.borrow_mut() {
+                f(&gl, &self.window);
+            }
+
+            self.window.swap_buffers().expect("Failed to swap buffers");
+        }
+    }
+
+    /// Doesn't touch `self` on purpose: rendering to a file uses its own headless
+    /// context (see `render_scene_to_file`), so it mustn't require a window (and the
+    /// display that comes with it) to already exist. `path` must have a `.ppm`
+    /// extension — output is always binary PPM, never PNG.
+    pub fn render_to_file(width: u32, height: u32, path: &Path) -> io::Result<()> {
+        render_scene_to_file(width, height, path)
+    }
+}
+
+
+#[cfg(any(feature = "gtk_3_16", feature = "glutin_backend"))]
+pub fn compile_shader(gl: &Gl, src: &str, ty: GLenum) -> Result<GLuint, ShaderError> {
     unsafe {
-        let shader = gl::CreateShader(ty);
+        let shader = gl.CreateShader(ty);
         let c_str = CString::new(src.as_bytes()).unwrap();
 
-        gl::ShaderSource(shader, 1, &c_str.as_ptr(), ptr::null());
-        gl::CompileShader(shader);
+        gl.ShaderSource(shader, 1, &c_str.as_ptr(), ptr::null());
+        gl.CompileShader(shader);
 
         let mut status = gl::FALSE as GLint;
-        gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut status);
+        gl.GetShaderiv(shader, gl::COMPILE_STATUS, &mut status);
 
         if status != (gl::TRUE as GLint) {
             let mut len = 0;
-            gl::GetShaderiv(shader, gl::INFO_LOG_LENGTH, &mut len);
+            gl.GetShaderiv(shader, gl::INFO_LOG_LENGTH, &mut len);
 
             let mut buf = Vec::with_capacity(len as usize);
-            gl::GetShaderInfoLog(
+            gl.GetShaderInfoLog(
                 shader,
                 len,
                 ptr::null_mut(),
                 buf.as_mut_ptr() as *mut GLchar,
             );
+            // `len` (and what GetShaderInfoLog writes) includes the trailing NUL.
+            buf.set_len((len as usize).saturating_sub(1));
 
-            panic!(
-                "{}",
-                str::from_utf8(&buf).ok().expect(
-                    "ShaderLogInfo not valid UTF-8",
-                )
-            );
+            gl.DeleteShader(shader);
+
+            return Err(ShaderError {
+                log: str::from_utf8(&buf)
+                    .unwrap_or("ShaderLogInfo not valid UTF-8")
+                    .to_string(),
+            });
         }
-        shader
+        Ok(shader)
     }
 }
 
-#[cfg(feature = "gtk_3_16")]
-pub fn link_program(vs: GLuint, fs: GLuint) -> GLuint {
+#[cfg(any(feature = "gtk_3_16", feature = "glutin_backend"))]
+pub fn link_program(gl: &Gl, vs: GLuint, fs: GLuint) -> Result<GLuint, ShaderError> {
     unsafe {
-        let program = gl::CreateProgram();
-        gl::AttachShader(program, vs);
-        gl::AttachShader(program, fs);
-        gl::LinkProgram(program);
+        let program = gl.CreateProgram();
+        gl.AttachShader(program, vs);
+        gl.AttachShader(program, fs);
+        gl.LinkProgram(program);
 
         let mut status = gl::FALSE as GLint;
-        gl::GetProgramiv(program, gl::LINK_STATUS, &mut status);
+        gl.GetProgramiv(program, gl::LINK_STATUS, &mut status);
 
         if status != (gl::TRUE as GLint) {
             let mut len: GLint = 0;
-            gl::GetProgramiv(program, gl::INFO_LOG_LENGTH, &mut len);
+            gl.GetProgramiv(program, gl::INFO_LOG_LENGTH, &mut len);
 
             let mut buf = Vec::with_capacity(len as usize);
-            gl::GetProgramInfoLog(
+            gl.GetProgramInfoLog(
                 program,
                 len,
                 ptr::null_mut(),
                 buf.as_mut_ptr() as *mut GLchar,
             );
+            // `len` (and what GetProgramInfoLog writes) includes the trailing NUL.
+            buf.set_len((len as usize).saturating_sub(1));
 
-            panic!(
-                "{}",
-                str::from_utf8(&buf).ok().expect(
-                    "ProgramLogInfo not valid UTF-8",
-                )
-            );
+            gl.DeleteProgram(program);
+            gl.DeleteShader(vs);
+            gl.DeleteShader(fs);
+
+            return Err(ShaderError {
+                log: str::from_utf8(&buf)
+                    .unwrap_or("ProgramLogInfo not valid UTF-8")
+                    .to_string(),
+            });
+        }
+
+        // Flags `vs`/`fs` for deletion now that they're linked into `program`; the
+        // driver keeps them alive until `program` is deleted too, so callers don't
+        // need to (and can't, short of `glDetachShader`) hang onto the handles.
+        gl.DeleteShader(vs);
+        gl.DeleteShader(fs);
+
+        Ok(program)
+    }
+}
+
+/// Where the interactive demo and `render_scene_to_file` both find `quad.vert`/
+/// `quad.frag`, so there's exactly one place that knows the shader directory layout.
+#[cfg(any(feature = "gtk_3_16", feature = "glutin_backend"))]
+fn quad_shader_paths() -> (PathBuf, PathBuf) {
+    let shader_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("shaders");
+    (shader_dir.join("quad.vert"), shader_dir.join("quad.frag"))
+}
+
+/**
+ * Renders the quad demo into an offscreen FBO (color + depth renderbuffers) using a
+ * headless context and writes the result out as a binary PPM. This is the same
+ * `HeadlessRendererBuilder` trick `connect_realize` already uses to load functions
+ * without a visible window, just pointed at an actual framebuffer instead of being
+ * dropped right after `Gl::load_with`. Loads `quad.vert`/`quad.frag` through
+ * `shader::preprocess` and compiles/links them with `compile_shader`/`link_program`,
+ * the same as the interactive demo, so there is one source of truth for the shaders
+ * and editing them also changes what `render_to_file` produces.
+ */
+#[cfg(any(feature = "gtk_3_16", feature = "glutin_backend"))]
+fn render_scene_to_file(width: u32, height: u32, path: &Path) -> io::Result<()> {
+    let context = glutin::HeadlessRendererBuilder::new(width as u32, height as u32)
+        .with_gl(GlRequest::Specific(Api::OpenGl, (3, 3)))
+        .build_strict()
+        .expect("Failed to create headless context");
+
+    unsafe {
+        context.make_current().expect("Failed to make headless context current");
+    }
+
+    let gl = Arc::new(Gl::load_with(|s| context.get_proc_address(s) as *const c_void));
+
+    #[cfg(feature = "gl_debug")]
+    enable_debug_output(&gl);
+
+    let pixels = unsafe {
+        let verts: [f32; 8] = [
+             0.5,  0.5,
+            -0.5,  0.5,
+            -0.5, -0.5,
+             0.5, -0.5
+        ];
+        let indices: [u16; 6] = [
+            0, 1, 2,
+            2, 3, 0
+        ];
+
+        let (vert_path, frag_path) = quad_shader_paths();
+
+        let vert_src = shader::preprocess(&vert_path)
+            .unwrap_or_else(|e| panic!("failed to read {:?}: {}", vert_path, e));
+        let frag_src = shader::preprocess(&frag_path)
+            .unwrap_or_else(|e| panic!("failed to read {:?}: {}", frag_path, e));
+
+        let vert_shader = compile_shader(&gl, &vert_src, gl::VERTEX_SHADER)
+            .unwrap_or_else(|e| panic!("vertex shader failed to compile:\n{}", e));
+        let frag_shader = compile_shader(&gl, &frag_src, gl::FRAGMENT_SHADER)
+            .unwrap_or_else(|e| panic!("fragment shader failed to compile:\n{}", e));
+        let prog = gl_object::Program::link(&gl, vert_shader, frag_shader)
+            .unwrap_or_else(|e| panic!("shader program failed to link:\n{}", e));
+
+        let vbo = gl_object::Buffer::new(&gl, gl::ARRAY_BUFFER);
+        vbo.set_data(&verts, gl::STATIC_DRAW);
+
+        let ebo = gl_object::Buffer::new(&gl, gl::ELEMENT_ARRAY_BUFFER);
+        ebo.set_data(&indices, gl::STATIC_DRAW);
+
+        let vao = gl_object::VertexArray::new(&gl);
+
+        let fbo = gl_object::Framebuffer::with_renderbuffers(&gl, width as GLsizei, height as GLsizei);
+        let _fbo_binding = fbo.bind();
+
+        gl_check!(gl, gl.Viewport(0, 0, width as GLsizei, height as GLsizei));
+        gl.ClearColor(0.0, 0.0, 0.0, 1.0);
+        gl_check!(gl, gl.Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT));
+
+        {
+            let _vao_binding = vao.bind();
+            let _vbo_binding = vbo.bind();
+            let _ebo_binding = ebo.bind();
+            let _prog_binding = prog.bind();
+
+            gl.EnableVertexAttribArray(0);
+            gl.VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, 0, 0 as *mut c_void);
+            gl_check!(gl, gl.DrawElements(gl::TRIANGLES, 6, gl::UNSIGNED_SHORT, 0 as *mut c_void));
+            gl.DisableVertexAttribArray(0);
+        }
+
+        let mut pixels = vec![0u8; (width * height * 4) as usize];
+        gl.ReadPixels(
+            0, 0,
+            width as GLsizei, height as GLsizei,
+            gl::RGBA, gl::UNSIGNED_BYTE,
+            pixels.as_mut_ptr() as *mut c_void,
+        );
+
+        pixels
+    };
+
+    drop(context);
+
+    write_ppm(path, width, height, &pixels)
+}
+
+/**
+ * Writes `rgba` as a binary PPM (P6). OpenGL's origin is bottom-left but PPM rows run
+ * top-to-bottom, so the rows are flipped on the way out. `path` must end in `.ppm` —
+ * there's no PNG encoder here, so silently accepting any extension would leave PPM
+ * bytes sitting under a misleading `.png` name.
+ */
+#[cfg(any(feature = "gtk_3_16", feature = "glutin_backend"))]
+fn write_ppm(path: &Path, width: u32, height: u32, rgba: &[u8]) -> io::Result<()> {
+    let has_ppm_extension = path
+        .extension()
+        .map_or(false, |ext| ext.eq_ignore_ascii_case("ppm"));
+    if !has_ppm_extension {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("render_to_file only writes binary PPM (P6); {:?} needs a .ppm extension", path),
+        ));
+    }
+
+    let mut file = File::create(path)?;
+    write!(file, "P6\n{} {}\n255\n", width, height)?;
+
+    for row in (0..height).rev() {
+        let row_start = (row * width * 4) as usize;
+        for pixel in rgba[row_start..row_start + (width * 4) as usize].chunks(4) {
+            file.write_all(&pixel[0..3])?;
         }
-        program
     }
+
+    Ok(())
 }
 
+/**
+ * `--render-to-file <path.ppm> [width] [height]` switches `main` from the
+ * interactive demo over to a single headless `render_scene_to_file` pass
+ * (width/height default to the interactive window's 1200x800), so the offscreen
+ * subsystem actually has a call site for CI and thumbnail generation instead of
+ * sitting unused. `path` must end in `.ppm` — there's no PNG encoder, and
+ * `write_ppm` rejects other extensions rather than writing PPM bytes under them.
+ */
+#[cfg(any(feature = "gtk_3_16", feature = "glutin_backend"))]
+fn render_to_file_request() -> Option<(PathBuf, u32, u32)> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--render-to-file" {
+            let path = PathBuf::from(
+                args.next().expect("--render-to-file requires a path"),
+            );
+            let width = args.next().and_then(|s| s.parse().ok()).unwrap_or(1200);
+            let height = args.next().and_then(|s| s.parse().ok()).unwrap_or(800);
+            return Some((path, width, height));
+        }
+    }
+    None
+}
 
 #[cfg(feature = "gtk_3_16")]
 fn main() {
+    if let Some((path, width, height)) = render_to_file_request() {
+        GlWindow::render_to_file(width, height, &path)
+            .unwrap_or_else(|e| panic!("failed to render to {:?}: {}", path, e));
+        return;
+    }
+
     let app = GlWindow::init();
     let gl = app.get_gl();
 
@@ -154,10 +1190,12 @@ fn main() {
      *
      * Box<T> will NOT work in this situation.
      */
-    let vbo = Arc::new(Mutex::new(0));
-    let ebo = Arc::new(Mutex::new(0));
-    let vao = Arc::new(Mutex::new(0));
-    let prog = Arc::new(Mutex::new(0));
+    let vbo: Arc<Mutex<Option<gl_object::Buffer>>> = Arc::new(Mutex::new(None));
+    let ebo: Arc<Mutex<Option<gl_object::Buffer>>> = Arc::new(Mutex::new(None));
+    let vao: Arc<Mutex<Option<gl_object::VertexArray>>> = Arc::new(Mutex::new(None));
+    let prog: Arc<Mutex<Option<gl_object::Program>>> = Arc::new(Mutex::new(None));
+
+    let (vert_path, frag_path) = quad_shader_paths();
 
     gl.connect_create_context(|gl_area| {
             /**
@@ -170,28 +1208,48 @@ fn main() {
                 Err(error) => panic!("{:?}", error),
             };
             gl_context.set_required_version(3, 0);
+            #[cfg(feature = "gl_debug")]
+            gl_context.set_debug_enabled(true);
             gl_context
         });
 
-    gl.connect_resize(|_gl_area, width, height| {
-            unsafe {
-                gl::Viewport(0, 0, width, height);
-            }
-        });
+    /**
+     * Populated once `connect_realize` loads this window's own `Gl` instance. Shared
+     * the same way `vbo`/`ebo`/`vao`/`prog` are, since `connect_resize` is wired up
+     * before the context exists and only needs the instance once GTK actually fires
+     * the resize signal (after realize).
+     */
+    let gl_ctx: Arc<Mutex<Option<Arc<Gl>>>> = Arc::new(Mutex::new(None));
+
+    {
+        let gl_ctx = gl_ctx.clone();
+        gl.connect_resize(move |_gl_area, width, height| {
+                if let Some(ref gl) = *gl_ctx.lock().unwrap() {
+                    unsafe {
+                        gl_check!(gl, gl.Viewport(0, 0, width, height));
+                    }
+                }
+            });
+    }
+
+    let reloader = Arc::new(Mutex::new(None));
 
     {
         let (vbo, ebo, vao, prog) = (vbo.clone(), ebo.clone(), vao.clone(), prog.clone());
+        let (vert_path, frag_path) = (vert_path.clone(), frag_path.clone());
+        let reloader = reloader.clone();
+        let gl_ctx = gl_ctx.clone();
         gl.connect_realize(move |gl_area| {
                 gl_area.get_context().unwrap().make_current();
 
                 /**
                  * This is a dummy context that we're using to load opengl functions. There
                  * are more elegant solutions than this one, but you must use this method if
-                 * you want your software to compile and run on Windows. 
+                 * you want your software to compile and run on Windows.
                  *
                  * Windows OpenGL function loading works differently than on linux. On linux,
                  * I can query the system at anytime with a crate like `static_library` and
-                 * it will return all of the OpenGL functions that I request with 
+                 * it will return all of the OpenGL functions that I request with
                  * `gl::load_with()`. Windows OpenGL loading is context based, so you need to
                  * have a valid context that the system's OpenGL provider will recognize (WGL
                  * in this case). The `static_library` approach won't work on Windows for this
@@ -199,26 +1257,29 @@ fn main() {
                  *
                  * A context must be defined and must also declare which version of OpenGL it
                  * would like to use. Once this is done, you can query functions from the system
-                 * and load them with `gl::load_with()`. Fortunately, we don't have to have that
+                 * and load them with `Gl::load_with()`. Fortunately, we don't have to have that
                  * valid WGL context on windows in order to render, we just need some OpenGL
                  * context to be made current and OpenGL will draw to that. Some once we use
                  * the Headless Context from glutin to load the OpenGL functions, we can just drop
                  * it and not have to worry about it.
                  *
                  * The downside of this approach is really just the pulling in of a bunch of
-                 * extra dependencies which increases compile time. 
+                 * extra dependencies which increases compile time.
                  */
                 let context = glutin::HeadlessRendererBuilder::new(0, 0)
                     .with_gl(GlRequest::Specific(Api::OpenGl, (3, 0)))
                     .build_strict()
                     .unwrap();
 
-                gl::load_with(|s| {
+                let gl = Arc::new(Gl::load_with(|s| {
                     (context.get_proc_address(s) as *const c_void)
-                });
+                }));
 
                 drop(context);
 
+                #[cfg(feature = "gl_debug")]
+                enable_debug_output(&gl);
+
                 unsafe {
                     let verts: [f32; 8] = [
                          0.5,  0.5,
@@ -231,24 +1292,176 @@ fn main() {
                         2, 3, 0
                     ];
 
-                    let vert_shader_source = r"
-                        #version 330
+                    let vert_src = shader::preprocess(&vert_path)
+                        .unwrap_or_else(|e| panic!("failed to read {:?}: {}", vert_path, e));
+                    let frag_src = shader::preprocess(&frag_path)
+                        .unwrap_or_else(|e| panic!("failed to read {:?}: {}", frag_path, e));
 
-                        layout (location = 0) in vec2 position;
+                    let mut vbo = vbo.lock().unwrap();
+                    let mut ebo = ebo.lock().unwrap();
+                    let mut vao = vao.lock().unwrap();
+                    let mut prog = prog.lock().unwrap();
 
-                        void main() {
-                            gl_Position = vec4(position, 0.0, 1.0);
-                        }
-                    ";
-                    let frag_shader_source = r"
-                        #version 330
+                    let vert_shader = compile_shader(
+                        &gl,
+                        &vert_src,
+                        gl::VERTEX_SHADER,
+                    ).unwrap_or_else(|e| panic!("vertex shader failed to compile:\n{}", e));
+                    let frag_shader = compile_shader(
+                        &gl,
+                        &frag_src,
+                        gl::FRAGMENT_SHADER,
+                    ).unwrap_or_else(|e| panic!("fragment shader failed to compile:\n{}", e));
+                    *prog = Some(
+                        gl_object::Program::link(&gl, vert_shader, frag_shader)
+                            .unwrap_or_else(|e| panic!("shader program failed to link:\n{}", e)),
+                    );
+
+                    *reloader.lock().unwrap() = Some(
+                        ShaderHotReloader::new(gl.clone(), vert_path.clone(), frag_path.clone())
+                            .unwrap_or_else(|e| panic!("failed to start shader watcher: {}", e)),
+                    );
+
+                    let new_vbo = gl_object::Buffer::new(&gl, gl::ARRAY_BUFFER);
+                    new_vbo.set_data(&verts, gl::STATIC_DRAW);
+                    *vbo = Some(new_vbo);
 
-                        out vec4 color;
+                    let new_ebo = gl_object::Buffer::new(&gl, gl::ELEMENT_ARRAY_BUFFER);
+                    new_ebo.set_data(&indices, gl::STATIC_DRAW);
+                    *ebo = Some(new_ebo);
 
-                        void main() {
-                            color = vec4(1.0f, 1.0f, 1.0f, 1.0f);
+                    *vao = Some(gl_object::VertexArray::new(&gl));
+                }
+
+                *gl_ctx.lock().unwrap() = Some(gl);
+            });
+    }
+
+    {
+        let (vbo, ebo, vao, prog) = (vbo.clone(), ebo.clone(), vao.clone(), prog.clone());
+        let reloader = reloader.clone();
+        let gl_ctx = gl_ctx.clone();
+        gl.connect_render(move |gl_area, _context| {
+                let gl_ctx = gl_ctx.lock().unwrap();
+                let gl = gl_ctx.as_ref().unwrap();
+
+                unsafe {
+                    let now = SystemTime::now();
+                    let dur = now.duration_since(start).expect("RIP");
+                    let millis = dur.as_secs() * 1_000 + (dur.subsec_nanos() as u64) / 1_000_000;
+
+                    let t = ((millis % 2000) as f32) / 1000.0;
+
+                    if let Some(ref mut reloader) = *reloader.lock().unwrap() {
+                        if let Some(new_prog) = reloader.poll() {
+                            *prog.lock().unwrap() = Some(new_prog);
                         }
-                    ";
+                    }
+
+                    gl.ClearColor(t / 2.0, 1.0 - (t / 2.0), 1.0, 1.0);
+                    gl_check!(gl, gl.Clear(gl::COLOR_BUFFER_BIT));
+
+                    let vbo = vbo.lock().unwrap();
+                    let vao = vao.lock().unwrap();
+                    let ebo = ebo.lock().unwrap();
+                    let prog = prog.lock().unwrap();
+
+                    let _vao_binding = vao.as_ref().unwrap().bind();
+                    let _vbo_binding = vbo.as_ref().unwrap().bind();
+                    let _ebo_binding = ebo.as_ref().unwrap().bind();
+                    let _prog_binding = prog.as_ref().unwrap().bind();
+
+                    gl.EnableVertexAttribArray(0);
+                    gl.VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, 0, 0 as *mut c_void);
+                    gl_check!(gl, gl.DrawElements(gl::TRIANGLES, 6, gl::UNSIGNED_SHORT, 0 as *mut c_void));
+                    gl.DisableVertexAttribArray(0);
+                }
+
+                gl_area.queue_render();
+                gtk::Inhibit(false)
+            });
+    }
+
+    /**
+     * `vbo`/`ebo`/`vao`/`prog` live in `Arc<Mutex<Option<_>>>` that only drop at the
+     * end of `main`, well after GTK has destroyed the `GLArea`'s context once the
+     * window closes. Their `Drop` impls call `glDelete*`, which is UB with no current
+     * context, so free them here instead, in the "unrealize" handler GTK fires while
+     * the context is still current and about to be torn down.
+     */
+    {
+        let (vbo, ebo, vao, prog) = (vbo.clone(), ebo.clone(), vao.clone(), prog.clone());
+        let gl_ctx = gl_ctx.clone();
+        gl.connect_unrealize(move |gl_area| {
+                if let Some(context) = gl_area.get_context() {
+                    context.make_current();
+                }
+
+                *prog.lock().unwrap() = None;
+                *vao.lock().unwrap() = None;
+                *ebo.lock().unwrap() = None;
+                *vbo.lock().unwrap() = None;
+                *reloader.lock().unwrap() = None;
+                *gl_ctx.lock().unwrap() = None;
+            });
+    }
+
+    app.exec();
+}
+
+#[cfg(feature = "glutin_backend")]
+fn main() {
+    if let Some((path, width, height)) = render_to_file_request() {
+        GlWindow::render_to_file(width, height, &path)
+            .unwrap_or_else(|e| panic!("failed to render to {:?}: {}", path, e));
+        return;
+    }
+
+    let app = GlWindow::init();
+    let start = SystemTime::now();
+    let vbo: Arc<Mutex<Option<gl_object::Buffer>>> = Arc::new(Mutex::new(None));
+    let ebo: Arc<Mutex<Option<gl_object::Buffer>>> = Arc::new(Mutex::new(None));
+    let vao: Arc<Mutex<Option<gl_object::VertexArray>>> = Arc::new(Mutex::new(None));
+    let prog: Arc<Mutex<Option<gl_object::Program>>> = Arc::new(Mutex::new(None));
+
+    let (vert_path, frag_path) = quad_shader_paths();
+
+    app.connect_create_context(|window| {
+        assert_eq!(window.get_api(), Api::OpenGl);
+    });
+
+    app.connect_resize(|gl, width, height| {
+            unsafe {
+                gl_check!(gl, gl.Viewport(0, 0, width as i32, height as i32));
+            }
+        });
+
+    let reloader = Arc::new(Mutex::new(None));
+
+    {
+        let (vbo, ebo, vao, prog) = (vbo.clone(), ebo.clone(), vao.clone(), prog.clone());
+        let (vert_path, frag_path) = (vert_path.clone(), frag_path.clone());
+        let reloader = reloader.clone();
+        app.connect_realize(move |gl, _window| {
+                #[cfg(feature = "gl_debug")]
+                enable_debug_output(gl);
+
+                unsafe {
+                    let verts: [f32; 8] = [
+                         0.5,  0.5,
+                        -0.5,  0.5,
+                        -0.5, -0.5,
+                         0.5, -0.5
+                    ];
+                    let indices: [u16; 6] = [
+                        0, 1, 2,
+                        2, 3, 0
+                    ];
+
+                    let vert_src = shader::preprocess(&vert_path)
+                        .unwrap_or_else(|e| panic!("failed to read {:?}: {}", vert_path, e));
+                    let frag_src = shader::preprocess(&frag_path)
+                        .unwrap_or_else(|e| panic!("failed to read {:?}: {}", frag_path, e));
 
                     let mut vbo = vbo.lock().unwrap();
                     let mut ebo = ebo.lock().unwrap();
@@ -256,43 +1469,42 @@ fn main() {
                     let mut prog = prog.lock().unwrap();
 
                     let vert_shader = compile_shader(
-                        vert_shader_source,
+                        gl,
+                        &vert_src,
                         gl::VERTEX_SHADER,
-                    );
+                    ).unwrap_or_else(|e| panic!("vertex shader failed to compile:\n{}", e));
                     let frag_shader = compile_shader(
-                        frag_shader_source,
+                        gl,
+                        &frag_src,
                         gl::FRAGMENT_SHADER,
+                    ).unwrap_or_else(|e| panic!("fragment shader failed to compile:\n{}", e));
+                    *prog = Some(
+                        gl_object::Program::link(gl, vert_shader, frag_shader)
+                            .unwrap_or_else(|e| panic!("shader program failed to link:\n{}", e)),
                     );
-                    *prog = link_program(vert_shader, frag_shader);
-
-                    gl::GenBuffers(1, &mut *vbo);
-                    gl::GenBuffers(1, &mut *ebo);
-                    gl::GenVertexArrays(1, &mut *vao);
-
-                    gl::BindBuffer(gl::ARRAY_BUFFER, *vbo);
-                    gl::BufferData(
-                        gl::ARRAY_BUFFER,
-                        8 * 4 /*8x f32*/,
-                        mem::transmute(&verts),
-                        gl::STATIC_DRAW,
-                    );
-                    gl::BindBuffer(gl::ARRAY_BUFFER, 0);
-
-                    gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, *ebo);
-                    gl::BufferData(
-                        gl::ELEMENT_ARRAY_BUFFER,
-                        6 * 2 /*6x i16*/,
-                        mem::transmute(&indices),
-                        gl::STATIC_DRAW,
+
+                    *reloader.lock().unwrap() = Some(
+                        ShaderHotReloader::new(gl.clone(), vert_path.clone(), frag_path.clone())
+                            .unwrap_or_else(|e| panic!("failed to start shader watcher: {}", e)),
                     );
-                    gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, 0);
+
+                    let new_vbo = gl_object::Buffer::new(gl, gl::ARRAY_BUFFER);
+                    new_vbo.set_data(&verts, gl::STATIC_DRAW);
+                    *vbo = Some(new_vbo);
+
+                    let new_ebo = gl_object::Buffer::new(gl, gl::ELEMENT_ARRAY_BUFFER);
+                    new_ebo.set_data(&indices, gl::STATIC_DRAW);
+                    *ebo = Some(new_ebo);
+
+                    *vao = Some(gl_object::VertexArray::new(gl));
                 }
             });
     }
 
     {
         let (vbo, ebo, vao, prog) = (vbo.clone(), ebo.clone(), vao.clone(), prog.clone());
-        gl.connect_render(move |gl_area, _context| {
+        let reloader = reloader.clone();
+        app.connect_render(move |gl, _window| {
                 unsafe {
                     let now = SystemTime::now();
                     let dur = now.duration_since(start).expect("RIP");
@@ -300,38 +1512,37 @@ fn main() {
 
                     let t = ((millis % 2000) as f32) / 1000.0;
 
-                    gl::ClearColor(t / 2.0, 1.0 - (t / 2.0), 1.0, 1.0);
-                    gl::Clear(gl::COLOR_BUFFER_BIT);
+                    if let Some(ref mut reloader) = *reloader.lock().unwrap() {
+                        if let Some(new_prog) = reloader.poll() {
+                            *prog.lock().unwrap() = Some(new_prog);
+                        }
+                    }
+
+                    gl.ClearColor(t / 2.0, 1.0 - (t / 2.0), 1.0, 1.0);
+                    gl_check!(gl, gl.Clear(gl::COLOR_BUFFER_BIT));
 
                     let vbo = vbo.lock().unwrap();
                     let vao = vao.lock().unwrap();
                     let ebo = ebo.lock().unwrap();
                     let prog = prog.lock().unwrap();
 
-                    gl::BindVertexArray(*vao);
-                    gl::BindBuffer(gl::ARRAY_BUFFER, *vbo);
-                    gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, *ebo);
-                    gl::UseProgram(*prog);
-                    gl::EnableVertexAttribArray(0);
-                    gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, 0, 0 as *mut c_void);
-                    gl::DrawElements(gl::TRIANGLES, 6, gl::UNSIGNED_SHORT, 0 as *mut c_void);
-                    gl::DisableVertexAttribArray(0);
-
-                    gl::UseProgram(0);
-                    gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, 0);
-                    gl::BindBuffer(gl::ARRAY_BUFFER, 0);
-                    gl::BindVertexArray(0);
-                }
+                    let _vao_binding = vao.as_ref().unwrap().bind();
+                    let _vbo_binding = vbo.as_ref().unwrap().bind();
+                    let _ebo_binding = ebo.as_ref().unwrap().bind();
+                    let _prog_binding = prog.as_ref().unwrap().bind();
 
-                gl_area.queue_render();
-                gtk::Inhibit(false)
+                    gl.EnableVertexAttribArray(0);
+                    gl.VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, 0, 0 as *mut c_void);
+                    gl_check!(gl, gl.DrawElements(gl::TRIANGLES, 6, gl::UNSIGNED_SHORT, 0 as *mut c_void));
+                    gl.DisableVertexAttribArray(0);
+                }
             });
     }
 
     app.exec();
 }
 
-#[cfg(not(feature = "gtk_3_16"))]
+#[cfg(not(any(feature = "gtk_3_16", feature = "glutin_backend")))]
 fn main() {
-    println!("You must compile with `--features gtk_3_16`!");
+    println!("You must compile with `--features gtk_3_16` or `--features glutin_backend`!");
 }